@@ -0,0 +1,94 @@
+//! Accessibility audit: plays scripted games, captures everything written to
+//! the player and checks it against rules a screen reader or narrow terminal
+//! depends on.
+
+use super::*;
+use crate::testing::play_script;
+
+/// The widest line the game may print
+const MAX_WIDTH: usize = 80;
+
+/// Every rule `output` breaks, one message per offending line
+pub fn audit(output: &str) -> Vec<String> {
+    let mut violations = Vec::new();
+
+    for (number, line) in output.lines().enumerate() {
+        let width = line.chars().count();
+        if width > MAX_WIDTH {
+            violations.push(format!(
+                "line {} is {} characters wide: {:?}",
+                number + 1,
+                width,
+                line
+            ));
+        }
+        // Also catches ANSI escapes, so color can't carry information alone
+        if line.chars().any(char::is_control) {
+            violations.push(format!(
+                "line {} has a control character: {:?}",
+                number + 1,
+                line
+            ));
+        }
+    }
+    violations
+}
+
+/// Play a game with `script` as the player's input and return its output
+fn capture(script: &str, show_interludes: bool) -> String {
+    play_script(
+        script.as_bytes(),
+        Options {
+            show_interludes,
+            ..Options::default()
+        },
+    )
+}
+
+fn assert_accessible(output: &str) {
+    let violations = audit(output);
+    assert!(violations.is_empty(), "{}", violations.join("\n"));
+}
+
+#[test]
+fn test_scripted_games() {
+    for script in &[
+        "travel\n",
+        "rest 3\n",
+        "rest 30000\n",
+        "rest lots and lots of days\n",
+        "hunt\n",
+        "dance\n",
+        "upgrade bed\n",
+        "upgrade \u{1b}[2J\n",
+        "travel\nupgrade bed\n",
+        "options interludes off\ntravel\n",
+        "options \u{1b}[2J on\n",
+        "",
+        "\u{1b}[2J\n",
+        "rest \u{1b}[2J\n",
+        "travel \u{1b}[31mX\n",
+    ] {
+        assert_accessible(&capture(script, true));
+        assert_accessible(&capture(script, false));
+    }
+
+    let overlong = format!("rest {}\n", "a".repeat(100));
+    assert_accessible(&capture(&overlong, true));
+    assert_accessible(&capture(&overlong, false));
+}
+
+#[test]
+fn test_interludes() {
+    for interlude in interludes::all() {
+        assert_accessible(&interlude.text);
+    }
+}
+
+#[test]
+fn test_audit_catches_violations() {
+    assert_eq!(audit(&"a".repeat(MAX_WIDTH)).len(), 0);
+    assert_eq!(audit(&"a".repeat(MAX_WIDTH + 1)).len(), 1);
+    assert_eq!(audit("\u{1b}[31mDysentery\u{1b}[0m").len(), 1);
+    assert_eq!(audit("tab\tseparated").len(), 1);
+}
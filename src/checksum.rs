@@ -0,0 +1,73 @@
+use super::{save, State};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The checksum before any action has been taken
+pub const INITIAL: u64 = FNV_OFFSET_BASIS;
+
+/// Fold `state` into the running checksum of everything before it
+///
+/// Hashes the State's save line with FNV-1a, which, unlike std's hasher, gives
+/// the same answer on every platform and Rust version, so checksums from two
+/// machines can be compared.
+pub fn roll(previous: u64, state: &State) -> u64 {
+    let mut line = Vec::new();
+    save::write_state(&mut line, state).expect("writing to a Vec can't fail");
+
+    line.iter().fold(previous, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// The running checksum after each State in a history
+pub fn rolling<'a, I: IntoIterator<Item = &'a State>>(states: I) -> Vec<u64> {
+    states
+        .into_iter()
+        .scan(INITIAL, |checksum, state| {
+            *checksum = roll(*checksum, state);
+            Some(*checksum)
+        })
+        .collect()
+}
+
+/// The first action at which two runs' checksums stop agreeing
+///
+/// Runs of different lengths diverge where the shorter one ends.
+pub fn first_divergence(ours: &[u64], theirs: &[u64]) -> Option<usize> {
+    match ours.iter().zip(theirs).position(|(a, b)| a != b) {
+        Some(index) => Some(index),
+        None if ours.len() != theirs.len() => Some(ours.len().min(theirs.len())),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::travel_history;
+
+    #[test]
+    fn test_rolling() {
+        let states = travel_history(5);
+        let checksums = rolling(&states);
+
+        assert_eq!(checksums.len(), 5);
+        assert_eq!(checksums, rolling(&states));
+        assert_eq!(checksums[0], roll(INITIAL, &states[0]));
+        assert_eq!(checksums[1], roll(checksums[0], &states[1]));
+        assert_ne!(checksums[1], roll(INITIAL, &states[1]));
+    }
+
+    #[test]
+    fn test_first_divergence() {
+        let states = travel_history(5);
+        let mut desynced = states.clone();
+        desynced[2].food += 1;
+
+        let ours = rolling(&states);
+        assert_eq!(first_divergence(&ours, &ours), None);
+        assert_eq!(first_divergence(&ours, &rolling(&desynced)), Some(2));
+        assert_eq!(first_divergence(&ours, &ours[..3]), Some(3));
+    }
+}
@@ -0,0 +1,185 @@
+use super::Upgrade;
+
+/// A command the player typed, already checked to be within sane bounds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    Travel,
+    Rest(i64),
+    Hunt,
+    Upgrade(Upgrade),
+    /// Turn the milestone interludes on or off
+    Interludes(bool),
+}
+
+/// Fewest and most days the party can rest in one go
+const REST_DAYS: (u64, u64) = (1, 30);
+
+/// Parse a line of player input, explaining what's wrong if it can't be used
+///
+/// Explanations never repeat what was typed, so input can't put escape
+/// sequences or overlong lines on the player's screen.
+///
+/// Returns None for commands the game doesn't know at all.
+pub fn parse(line: &str) -> Option<Result<Command, String>> {
+    let mut words = line.split_whitespace();
+
+    match words.next()? {
+        "travel" => Some(no_more_arguments(words, "travel").map(|_| Command::Travel)),
+        "rest" => Some(
+            number(words.next(), "rest", "days", REST_DAYS)
+                .and_then(|days| no_more_arguments(words, "rest").map(|_| days))
+                .map(|days| Command::Rest(days as i64)),
+        ),
+        "hunt" => Some(no_more_arguments(words, "hunt").map(|_| Command::Hunt)),
+        "upgrade" => Some(
+            upgrade(words.next())
+                .and_then(|upgrade| no_more_arguments(words, "upgrade").map(|_| upgrade))
+                .map(Command::Upgrade),
+        ),
+        "options" => Some(
+            setting(words.next(), words.next())
+                .and_then(|setting| no_more_arguments(words, "options").map(|_| setting)),
+        ),
+        _ => None,
+    }
+}
+
+/// Parse a numeric argument, which must be a whole number from min to max
+fn number(
+    argument: Option<&str>,
+    command: &str,
+    unit: &str,
+    bounds: (u64, u64),
+) -> Result<u64, String> {
+    let (min, max) = bounds;
+    let out_of_bounds = || {
+        format!(
+            "You can only {} for {} to {} {} at a time.",
+            command, min, max, unit
+        )
+    };
+
+    let argument =
+        argument.ok_or_else(|| format!("How many {}? Try {} {}.", unit, command, min))?;
+    let digits = argument.strip_prefix('-').unwrap_or(argument);
+    if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("That isn't a number of {}.", unit));
+    }
+    // Negative, or too long for a u64, is still just out of bounds
+    let value: u64 = match argument.parse() {
+        Ok(value) => value,
+        Err(_) => return Err(out_of_bounds()),
+    };
+
+    if value < min || value > max {
+        Err(out_of_bounds())
+    } else {
+        Ok(value)
+    }
+}
+
+/// Parse the name of a wagon upgrade
+fn upgrade(argument: Option<&str>) -> Result<Upgrade, String> {
+    match argument {
+        Some("bed") => Ok(Upgrade::LargerBed),
+        Some(_) => Err("That isn't an upgrade. Try upgrade bed.".to_string()),
+        None => Err("Upgrade what? Try upgrade bed.".to_string()),
+    }
+}
+
+/// Parse the setting and value after options
+fn setting(name: Option<&str>, value: Option<&str>) -> Result<Command, String> {
+    if name != Some("interludes") {
+        return Err("Interludes are all there is to set. Try options interludes off.".to_string());
+    }
+    match value {
+        Some("on") => Ok(Command::Interludes(true)),
+        Some("off") => Ok(Command::Interludes(false)),
+        _ => Err("Interludes can be on or off.".to_string()),
+    }
+}
+
+fn no_more_arguments<'a, I: Iterator<Item = &'a str>>(
+    mut words: I,
+    command: &str,
+) -> Result<(), String> {
+    match words.next() {
+        Some(_) => Err(format!("{} doesn't take anything more.", command)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("travel"), Some(Ok(Command::Travel)));
+        assert_eq!(parse("  rest 3 "), Some(Ok(Command::Rest(3))));
+        assert_eq!(parse("rest 30"), Some(Ok(Command::Rest(30))));
+        assert_eq!(parse("hunt"), Some(Ok(Command::Hunt)));
+        assert_eq!(
+            parse("upgrade bed"),
+            Some(Ok(Command::Upgrade(Upgrade::LargerBed)))
+        );
+        assert_eq!(
+            parse("options interludes off"),
+            Some(Ok(Command::Interludes(false)))
+        );
+        assert_eq!(parse("dance"), None);
+        assert_eq!(parse(""), None);
+    }
+
+    #[test]
+    fn test_absurd_numbers() {
+        let rejected = |line| match parse(line) {
+            Some(Err(message)) => message,
+            other => panic!("{:?} was accepted as {:?}", line, other),
+        };
+
+        assert_eq!(
+            rejected("rest 30000"),
+            "You can only rest for 1 to 30 days at a time."
+        );
+        assert_eq!(rejected("rest 0"), rejected("rest 30000"));
+        assert_eq!(rejected("rest -3"), rejected("rest 30000"));
+        assert_eq!(
+            rejected("rest 99999999999999999999999999"),
+            rejected("rest 30000")
+        );
+        assert_eq!(rejected("rest"), "How many days? Try rest 1.");
+        assert_eq!(rejected("rest lots"), "That isn't a number of days.");
+        assert_eq!(rejected("rest 2.5"), rejected("rest lots"));
+        assert_eq!(rejected("rest -"), rejected("rest lots"));
+        assert_eq!(rejected("rest 3 days"), "rest doesn't take anything more.");
+        assert_eq!(rejected("travel 900"), "travel doesn't take anything more.");
+        assert_eq!(rejected("hunt deer"), "hunt doesn't take anything more.");
+        assert_eq!(rejected("upgrade"), "Upgrade what? Try upgrade bed.");
+        assert_eq!(
+            rejected("upgrade wings"),
+            "That isn't an upgrade. Try upgrade bed."
+        );
+        assert_eq!(
+            rejected("upgrade bed now"),
+            "upgrade doesn't take anything more."
+        );
+        assert_eq!(
+            rejected("options"),
+            "Interludes are all there is to set. Try options interludes off."
+        );
+        assert_eq!(rejected("options color on"), rejected("options"));
+        assert_eq!(
+            rejected("options interludes"),
+            "Interludes can be on or off."
+        );
+        assert_eq!(
+            rejected("options interludes loud"),
+            rejected("options interludes")
+        );
+        assert_eq!(
+            rejected("options interludes on now"),
+            "options doesn't take anything more."
+        );
+    }
+}
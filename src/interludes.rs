@@ -0,0 +1,179 @@
+use super::{by_deadline, State};
+use chrono::prelude::*;
+
+const INTERLUDES: &str = include_str!("../data/interludes.txt");
+
+/// A short scene shown when the party reaches a milestone
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Interlude {
+    pub id: String,
+    /// Miles left to Oregon when the milestone is reached
+    pub miles: u64,
+    /// Month and day, in the year the party departed, the milestone must be
+    /// reached by, if any
+    pub deadline: Option<(u32, u32)>,
+    pub text: String,
+}
+
+impl Interlude {
+    fn on_time(&self, state: &State) -> bool {
+        match self.deadline {
+            Some(deadline) => by_deadline(state.date, state.departed, deadline),
+            None => true,
+        }
+    }
+}
+
+/// Parse every interlude from the data file, in the order they appear
+pub fn all() -> Vec<Interlude> {
+    parse(INTERLUDES).expect("data/interludes.txt should be valid")
+}
+
+/// Interludes reached by going from the `before` State to the `after` one
+///
+/// Miles only ever go down, so each interlude comes up once per run.
+pub fn reached(before: &State, after: &State) -> Vec<Interlude> {
+    all()
+        .into_iter()
+        .filter(|interlude| interlude.miles < before.miles && interlude.miles >= after.miles)
+        .filter(|interlude| interlude.on_time(after))
+        .collect()
+}
+
+/// The interlude shown before the first action of a run
+pub fn opening(state: &State) -> Vec<Interlude> {
+    all()
+        .into_iter()
+        .filter(|interlude| interlude.miles >= state.miles)
+        .filter(|interlude| interlude.on_time(state))
+        .collect()
+}
+
+fn parse(data: &str) -> Result<Vec<Interlude>, String> {
+    let mut interludes: Vec<Interlude> = Vec::new();
+
+    for (number, line) in data.lines().enumerate() {
+        if line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix("= ") {
+            let mut fields = header.split_whitespace();
+            let id = fields
+                .next()
+                .ok_or_else(|| format!("line {}: missing id", number + 1))?;
+            let miles = fields
+                .next()
+                .and_then(|miles| miles.parse().ok())
+                .ok_or_else(|| format!("line {}: missing or bad miles", number + 1))?;
+            let deadline = match (fields.next(), fields.next()) {
+                (None, _) => None,
+                (Some("by"), Some(deadline)) => Some(
+                    parse_deadline(deadline)
+                        .ok_or_else(|| format!("line {}: bad deadline", number + 1))?,
+                ),
+                _ => return Err(format!("line {}: expected by <MM-DD>", number + 1)),
+            };
+
+            interludes.push(Interlude {
+                id: id.to_string(),
+                miles,
+                deadline,
+                text: String::new(),
+            });
+        } else if let Some(interlude) = interludes.last_mut() {
+            interlude.text.push_str(line);
+            interlude.text.push('\n');
+        } else if !line.trim().is_empty() {
+            return Err(format!("line {}: text before any interlude", number + 1));
+        }
+    }
+
+    for interlude in &mut interludes {
+        interlude.text = interlude.text.trim().to_string();
+    }
+    Ok(interludes)
+}
+
+fn parse_deadline(deadline: &str) -> Option<(u32, u32)> {
+    let mut parts = deadline.splitn(2, '-');
+    let month = parts.next()?.parse().ok()?;
+    let day = parts.next()?.parse().ok()?;
+    // Any leap year will do to check the day exists
+    NaiveDate::from_ymd_opt(2020, month, day).map(|_| (month, day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{start_of_trail, StateBuilder};
+
+    fn ids(interludes: &[Interlude]) -> Vec<&str> {
+        interludes.iter().map(|i| &i.id[..]).collect()
+    }
+
+    #[test]
+    fn test_data_file() {
+        let interludes = all();
+
+        assert_eq!(
+            ids(&interludes),
+            vec![
+                "independence",
+                "chimney-rock",
+                "independence-rock",
+                "independence-day",
+                "south-pass"
+            ]
+        );
+        assert!(interludes.iter().all(|i| !i.text.is_empty()));
+    }
+
+    #[test]
+    fn test_reached() {
+        let at = |miles| StateBuilder::new().miles(miles).build();
+
+        assert_eq!(ids(&opening(&start_of_trail())), vec!["independence"]);
+        assert!(reached(&at(2000), &at(1500)).is_empty());
+        assert_eq!(ids(&reached(&at(1500), &at(1446))), vec!["chimney-rock"]);
+        assert!(reached(&at(1446), &at(1400)).is_empty());
+        assert_eq!(reached(&at(1500), &at(1100)).len(), 3);
+    }
+
+    #[test]
+    fn test_reached_by_deadline() {
+        let before = StateBuilder::new().miles(1200).build();
+        let on_time = StateBuilder::new()
+            .miles(1170)
+            .date(Utc.ymd(2020, 7, 4))
+            .build();
+        let late = StateBuilder::new()
+            .miles(1170)
+            .date(Utc.ymd(2020, 7, 5))
+            .build();
+
+        assert_eq!(
+            ids(&reached(&before, &on_time)),
+            vec!["independence-rock", "independence-day"]
+        );
+        assert_eq!(ids(&reached(&before, &late)), vec!["independence-rock"]);
+
+        let next_year = StateBuilder::new()
+            .miles(1170)
+            .date(Utc.ymd(2021, 6, 1))
+            .build();
+        assert_eq!(
+            ids(&reached(&before, &next_year)),
+            vec!["independence-rock"]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("= chimney-rock\nText").is_err());
+        assert!(parse("= chimney-rock far\nText").is_err());
+        assert!(parse("Text\n= chimney-rock 1446").is_err());
+        assert!(parse("= independence-day 1170 07-04\nText").is_err());
+        assert!(parse("= independence-day 1170 by 02-30\nText").is_err());
+    }
+}
@@ -2,16 +2,103 @@
 
 use chrono::prelude::*;
 use chrono::Duration;
+use command::Command;
+use output::{OutputMode, Turn};
+use paths::Paths;
 use redux_rs::{Store};
 use std::boxed::Box;
+use std::path::PathBuf;
+
+#[cfg(test)]
+mod audit;
+mod checksum;
+mod command;
+mod interludes;
+mod output;
+mod paths;
+mod save;
+mod storage;
+#[cfg(test)]
+mod stress;
+#[cfg(test)]
+mod testing;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 struct State {
     date: Date<Utc>,
+    /// The day the party left Independence
+    departed: Date<Utc>,
     miles: u64,
     food: u64,
     health: u64,
     hunt_days: i64,
+    wagon: Wagon,
+    bounty: Bounty,
+    /// Reached Independence Rock by the Fourth of July, which scores a bonus
+    celebrated: bool,
+}
+
+/// How kind the land is this year, picked once per run
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Bounty {
+    Famine,
+    Normal,
+    Abundance,
+}
+
+/// Mixed into a run's seed so the bounty isn't drawn from the same stream
+/// as play()'s rolls, which would tie the year to the first leg's length
+const BOUNTY_STREAM: u64 = 0x9e37_79b9_7f4a_7c15;
+
+impl Bounty {
+    /// Pick the year for a run: one in five is a famine, one in five abundant
+    fn from_seed(seed: u64) -> Bounty {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        match StdRng::seed_from_u64(seed ^ BOUNTY_STREAM).gen_range(0, 5) {
+            0 => Bounty::Famine,
+            1 => Bounty::Abundance,
+            _ => Bounty::Normal,
+        }
+    }
+
+    /// Pounds of food one hunt brings in
+    fn hunt_yield(&self) -> u64 {
+        match self {
+            Bounty::Famine => 50,
+            Bounty::Normal => 100,
+            Bounty::Abundance => 150,
+        }
+    }
+}
+
+/// The party's wagon and whatever upgrades have been bought for it
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Wagon {
+    larger_bed: bool,
+}
+
+impl Wagon {
+    /// Pounds of food the wagon can carry
+    fn capacity(&self) -> u64 {
+        if self.larger_bed {
+            1500
+        } else {
+            1000
+        }
+    }
+
+    fn upgraded(mut self, upgrade: Upgrade) -> Wagon {
+        match upgrade {
+            Upgrade::LargerBed => self.larger_bed = true,
+        }
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Upgrade {
+    LargerBed,
 }
 
 enum Action<'a> {
@@ -21,12 +108,14 @@ enum Action<'a> {
     Rest(Duration),
     Status(Box<dyn Fn(State) -> State + 'a>),
     Travel(Duration, u64),
+    Upgrade(Upgrade),
 }
 
 enum SimpleAction {
     Hunt,
     Travel(Duration, u64),
     Rest(Duration),
+    Upgrade(Upgrade),
 }
 
 impl<'a> From<SimpleAction> for Action<'a> {
@@ -37,10 +126,23 @@ impl<'a> From<SimpleAction> for Action<'a> {
             Hunt => Action::Hunt,
             Travel(d, i) => Action::Travel(d, i),
             Rest(d) => Action::Rest(d),
+            Upgrade(u) => Action::Upgrade(u),
         }
     }
 }
 
+/// Miles left to Oregon at Independence Rock
+const INDEPENDENCE_ROCK: u64 = 1170;
+
+/// Month and day the party should reach Independence Rock by
+const INDEPENDENCE_DAY: (u32, u32) = (7, 4);
+
+/// Whether `date` is no later than a month and day in the year the party departed
+fn by_deadline(date: Date<Utc>, departed: Date<Utc>, deadline: (u32, u32)) -> bool {
+    let (month, day) = deadline;
+    (date.year(), date.month(), date.day()) <= (departed.year(), month, day)
+}
+
 /// The main function that uses an Action to get a new State
 ///
 /// # Examples
@@ -62,11 +164,28 @@ impl<'a> From<SimpleAction> for Action<'a> {
 fn root_reducer(state: &State, action: &Action) -> State {
     match action {
         // Travel: Move the player forward by distance and move the date forward by days
-        Action::Travel(days, distance) => State {ng)
-            date: state.date + *days,
-            miles: state.miles - *distance,
-            ..*state
-        },
+        Action::Travel(days, distance) => {
+            let date = state.date + *days;
+            // Never travel past the end of the trail
+            let miles = state.miles.saturating_sub(*distance);
+            // Reaching Independence Rock by the Fourth of July is celebrated,
+            // which regenerates one health (up to 5)
+            let celebrating = state.miles > INDEPENDENCE_ROCK
+                && miles <= INDEPENDENCE_ROCK
+                && by_deadline(date, state.departed, INDEPENDENCE_DAY);
+
+            State {
+                date,
+                miles,
+                health: if celebrating {
+                    (state.health + 1).min(5)
+                } else {
+                    state.health
+                },
+                celebrated: state.celebrated || celebrating,
+                ..*state
+            }
+        }
 
         // Rest: Regenerate one health (up to 5) by stopping for rest_days
         Action::Rest(days) => State {
@@ -80,10 +199,19 @@ fn root_reducer(state: &State, action: &Action) -> State {
             ..*state
         },
 
-        // Hunt: Add one hundred pounds of food by stopping for hunt_days
+        // Hunt: Add the year's hunt yield of food by stopping for hunt_days
         Action::Hunt => State {
             date: state.date + Duration::days(state.hunt_days),
-            food: state.food + 100,
+            // No more than the wagon can carry, but never throw food away
+            food: (state.food + state.bounty.hunt_yield())
+                .min(state.wagon.capacity())
+                .max(state.food),
+            ..*state
+        },
+
+        // Upgrade: Fit the wagon with an upgrade; buying the same one twice does nothing
+        Action::Upgrade(upgrade) => State {
+            wagon: state.wagon.upgraded(*upgrade),
             ..*state
         },
 
@@ -101,50 +229,255 @@ fn root_reducer(state: &State, action: &Action) -> State {
 fn main() {
     use rand::Rng;
     use std::io;
-    let inital_state = State {
+    let mut options = match Options::from_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+    };
+    let seed = *options.seed.get_or_insert_with(|| rand::thread_rng().gen());
+    // On stderr so --output json stays machine-readable
+    eprintln!(
+        "Seed {} (pass --seed {} to play this run again)",
+        seed, seed
+    );
+    let inital_state = new_game(seed);
+
+    // Nothing is saved yet, so a missing or unwritable data directory isn't fatal
+    match options.paths() {
+        Some(paths) => {
+            if let Err(error) = paths.create_all() {
+                eprintln!("Couldn't create {}: {}", paths.data.display(), error);
+            }
+        }
+        None => eprintln!("Couldn't find a home directory; pass --data-dir to choose one"),
+    }
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    if let Err(error) = play(&mut stdin.lock(), &mut stdout.lock(), inital_state, options) {
+        eprintln!("Couldn't talk to the terminal: {}", error);
+    }
+}
+
+/// The State a run starts with, in the year its seed picks
+fn new_game(seed: u64) -> State {
+    State {
         date: Utc.ymd(2020, 3, 1),
+        departed: Utc.ymd(2020, 3, 1),
         miles: 2000,
         food: 500,
         health: 5,
         hunt_days: 2,
-    };
-    let mut store = Store::new(root_reducer, inital_state);
+        wagon: Wagon::default(),
+        bounty: Bounty::from_seed(seed),
+        celebrated: false,
+    }
+}
 
-    let mut user_input = String::new();
+/// Settings for play() taken from the command line
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Options {
+    show_interludes: bool,
+    output: OutputMode,
+    data_dir: Option<PathBuf>,
+    /// Picks the year's bounty and every random roll; drawn fresh if None
+    seed: Option<u64>,
+}
 
-    println!("What is your action?");
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            show_interludes: true,
+            output: OutputMode::Text,
+            data_dir: None,
+            seed: None,
+        }
+    }
+}
 
-    // Store user input in user_input
-    match io::stdin().read_line(&mut user_input) {
-        Ok(_) => match &user_input[..] {
-            "travel" => &store.dispatch(Action::Travel(
-                // Random number between three and seven
-                Duration::days(rand::thread_rng().gen_range(3, 7)),
-                rand::thread_rng().gen_range(30, 60),
-            )),
-            _ => &println!("Uh oh! My creator tried, but was unable to implement that action. I've been kind of a pain.")
+impl Options {
+    fn from_args<I: Iterator<Item = String>>(mut args: I) -> Result<Options, String> {
+        let mut options = Options::default();
+
+        while let Some(arg) = args.next() {
+            match &arg[..] {
+                "--skip-interludes" => options.show_interludes = false,
+                "--output" => {
+                    options.output = match args.next().as_ref().map(|mode| &mode[..]) {
+                        Some("text") => OutputMode::Text,
+                        Some("json") => OutputMode::Json,
+                        _ => return Err("--output must be followed by text or json".to_string()),
+                    }
+                }
+                "--data-dir" => match args.next() {
+                    Some(data_dir) => options.data_dir = Some(PathBuf::from(data_dir)),
+                    None => return Err("--data-dir must be followed by a directory".to_string()),
+                },
+                "--seed" => match args.next().and_then(|seed| seed.parse().ok()) {
+                    Some(seed) => options.seed = Some(seed),
+                    None => return Err("--seed must be followed by a whole number".to_string()),
+                },
+                _ => return Err(format!("Unknown option {}", arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Where to keep files: --data-dir if given, or the platform's usual place
+    fn paths(&self) -> Option<Paths> {
+        match &self.data_dir {
+            Some(data_dir) => Some(Paths::under(data_dir)),
+            None => Paths::platform(),
+        }
+    }
+}
+
+/// Commands play() accepts until the trail is finished
+const CHOICES: &[&str] = &["travel", "rest", "hunt", "options"];
+
+/// Commands play() accepts before the first travel, while the wagon can
+/// still be fitted out
+const OUTFITTING_CHOICES: &[&str] = &["travel", "rest", "hunt", "options", "upgrade"];
+
+/// Run a game reading commands from `input` and writing everything to `output`
+///
+/// Plays until the party reaches Oregon or `input` runs out, writing one
+/// Turn at the start and one after every command.
+fn play<R, W>(
+    input: &mut R,
+    output: &mut W,
+    inital_state: State,
+    mut options: Options,
+) -> std::io::Result<()>
+where
+    R: std::io::BufRead,
+    W: std::io::Write,
+{
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+    let seed = options.seed.unwrap_or_else(|| rand::thread_rng().gen());
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut store = Store::new(root_reducer, inital_state);
+    let mut set_out = false;
+    let mut messages = Vec::new();
+
+    if options.show_interludes {
+        messages.extend(interlude_messages(&interludes::opening(store.state())));
+    }
+    messages.push("What is your action?".to_string());
+
+    output::write_turn(
+        output,
+        options.output,
+        &Turn {
+            before: None,
+            after: store.state(),
+            messages,
+            choices: OUTFITTING_CHOICES,
         },
-        Err(error) => &println!(
-            "Hmm, you put something really weird in here. The Rust language gave the error {}.",
-            error
-        ),
-    };
+    )?;
+
+    loop {
+        let state_before = *store.state();
+        let mut user_input = String::new();
+        let mut messages = Vec::new();
+
+        // Store user input in user_input
+        match input.read_line(&mut user_input) {
+            // The player closed the input, so stop where they left off
+            Ok(0) => return Ok(()),
+            Ok(_) => match command::parse(&user_input) {
+                Some(Ok(Command::Travel)) => {
+                    store.dispatch(Action::Travel(
+                        // Random number between three and seven
+                        Duration::days(rng.gen_range(3, 7)),
+                        rng.gen_range(30, 60),
+                    ));
+                    set_out = true;
+                    if options.show_interludes {
+                        messages.extend(interlude_messages(&interludes::reached(&state_before, store.state())));
+                    }
+                }
+                Some(Ok(Command::Rest(days))) => store.dispatch(Action::Rest(Duration::days(days))),
+                Some(Ok(Command::Hunt)) => store.dispatch(Action::Hunt),
+                Some(Ok(Command::Upgrade(_))) if set_out => {
+                    messages.push("The wagon can only be fitted out before setting out.".to_string())
+                }
+                Some(Ok(Command::Upgrade(upgrade))) => {
+                    store.dispatch(Action::Upgrade(upgrade));
+                    messages.push(match upgrade {
+                        Upgrade::LargerBed => format!(
+                            "The wagon now has a larger bed, with room for {} pounds of food.",
+                            store.state().wagon.capacity()
+                        ),
+                    });
+                }
+                Some(Ok(Command::Interludes(on))) => {
+                    options.show_interludes = on;
+                    messages.push(format!("Interludes are now {}.", if on { "on" } else { "off" }));
+                }
+                // A command we know, but with arguments that don't make sense
+                Some(Err(message)) => messages.push(message),
+                None => messages.push(
+                    "Uh oh! My creator tried, but was unable to implement that action.\nI've been kind of a pain.".to_string()
+                ),
+            },
+            // Not UTF-8; the bad line has been read past, so carry on
+            Err(error) if error.kind() == std::io::ErrorKind::InvalidData => messages.push(format!(
+                "Hmm, you put something really weird in here.\nThe Rust language gave the error {}.",
+                error
+            )),
+            Err(error) => return Err(error),
+        };
+
+        let finished = store.state().miles == 0;
+        messages.push(if finished {
+            "You made it to Oregon!".to_string()
+        } else {
+            "What is your action?".to_string()
+        });
+
+        output::write_turn(
+            output,
+            options.output,
+            &Turn {
+                before: Some(&state_before),
+                after: store.state(),
+                messages,
+                choices: if finished {
+                    &[]
+                } else if set_out {
+                    CHOICES
+                } else {
+                    OUTFITTING_CHOICES
+                },
+            },
+        )?;
+
+        if finished {
+            return Ok(());
+        }
+    }
+}
+
+/// Each interlude's text followed by a blank line
+fn interlude_messages(interludes: &[interludes::Interlude]) -> Vec<String> {
+    interludes
+        .iter()
+        .map(|interlude| format!("{}\n", interlude.text))
+        .collect()
 }
-#[cfg(test)]
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::{play_script, start_of_trail, StateBuilder};
     use std::cell::Cell;
 
     #[test]
     fn test_travel() {
-        let initial_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 2000,
-            food: 500,
-            health: 5,
-            hunt_days: 2,
-        };
+        let initial_state = start_of_trail();
 
         let result_state = State {
             miles: 1970,
@@ -183,16 +516,67 @@ mod tests {
             ),
             result_state_with_more_miles
         );
+
+        // Travelling further than is left stops at Oregon
+        let nearly_there = StateBuilder::new().miles(20).build();
+        assert_eq!(root_reducer(&nearly_there, &default_action).miles, 0);
+    }
+
+    #[test]
+    fn test_travel_to_independence_rock() {
+        let on_time_state = StateBuilder::new()
+            .date(Utc.ymd(2020, 7, 1))
+            .miles(1200)
+            .health(3)
+            .build();
+        let late_state = State {
+            date: Utc.ymd(2020, 7, 2),
+            ..on_time_state
+        };
+        let past_rock_state = State {
+            miles: 1170,
+            ..on_time_state
+        };
+        // Early in the year, but a whole year after setting out
+        let next_year_state = State {
+            date: Utc.ymd(2021, 6, 1),
+            ..on_time_state
+        };
+        let travel = SimpleAction::Travel(Duration::days(3), 30).into();
+
+        assert_eq!(root_reducer(&on_time_state, &travel).health, 4);
+        assert_eq!(root_reducer(&late_state, &travel).health, 3);
+        assert_eq!(root_reducer(&past_rock_state, &travel).health, 3);
+        assert_eq!(root_reducer(&next_year_state, &travel).health, 3);
+
+        assert!(root_reducer(&on_time_state, &travel).celebrated);
+        assert!(!root_reducer(&late_state, &travel).celebrated);
+        assert!(!root_reducer(&past_rock_state, &travel).celebrated);
+        assert!(!root_reducer(&next_year_state, &travel).celebrated);
+        // Once earned, the bonus is kept for the rest of the trail
+        let celebrated_state = State {
+            celebrated: true,
+            ..past_rock_state
+        };
+        assert!(root_reducer(&celebrated_state, &travel).celebrated);
+    }
+
+    #[test]
+    fn test_independence_rock_matches_interludes() {
+        let interludes = interludes::all();
+        let find = |id| interludes.iter().find(|i| i.id == id).unwrap();
+
+        assert_eq!(find("independence-rock").miles, INDEPENDENCE_ROCK);
+        assert_eq!(find("independence-day").miles, INDEPENDENCE_ROCK);
+        assert_eq!(find("independence-day").deadline, Some(INDEPENDENCE_DAY));
     }
 
     #[test]
     fn test_rest() {
-        let initial_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 2000,
-            food: 500,
-            health: 4,
-            hunt_days: 2,
+        let initial_state = StateBuilder::new().health(4).build();
+        let healthy_state = State {
+            health: 5,
+            ..initial_state
         };
         let duration = Duration::days(2);
 
@@ -200,26 +584,21 @@ mod tests {
             root_reducer(&initial_state, &SimpleAction::Rest(duration).into()),
             State {
                 date: Utc.ymd(2020, 3, 3),
+                health: 5,
                 ..initial_state
             }
         );
         assert_eq!(
-            root_reducer(&initial_state, &SimpleAction::Rest(duration).into()),
+            root_reducer(&healthy_state, &SimpleAction::Rest(duration).into()),
             State {
                 date: Utc.ymd(2020, 3, 3),
-                ..initial_state
+                ..healthy_state
             }
         );
     }
     #[test]
     fn test_hunt() {
-        let initial_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 2000,
-            food: 500,
-            health: 5,
-            hunt_days: 2,
-        };
+        let initial_state = start_of_trail();
         let state_with_more_days: State = State {
             hunt_days: 3,
             ..initial_state
@@ -247,19 +626,274 @@ mod tests {
     }
 
     #[test]
-    fn test_status() {
-        let default_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 1970,
-            food: 500,
-            health: 5,
-            hunt_days: 2,
+    fn test_hunt_with_full_wagon() {
+        let nearly_full_state = StateBuilder::new().food(950).build();
+        let overloaded_state = StateBuilder::new().food(1200).build();
+
+        assert_eq!(
+            root_reducer(&nearly_full_state, &SimpleAction::Hunt.into()).food,
+            1000
+        );
+        assert_eq!(
+            root_reducer(&overloaded_state, &SimpleAction::Hunt.into()).food,
+            1200
+        );
+    }
+
+    #[test]
+    fn test_upgrade() {
+        let initial_state = StateBuilder::new().food(950).build();
+        let upgraded_state = State {
+            wagon: Wagon { larger_bed: true },
+            ..initial_state
         };
+        let upgrade: Action = SimpleAction::Upgrade(Upgrade::LargerBed).into();
+
+        assert_eq!(root_reducer(&initial_state, &upgrade), upgraded_state);
+        assert_eq!(root_reducer(&upgraded_state, &upgrade), upgraded_state);
+        assert_eq!(
+            root_reducer(&upgraded_state, &SimpleAction::Hunt.into()).food,
+            1050
+        );
+    }
+
+    #[test]
+    fn test_hunt_in_lean_and_good_years() {
+        let famine_state = StateBuilder::new().bounty(Bounty::Famine).build();
+        let abundant_state = StateBuilder::new().bounty(Bounty::Abundance).build();
+
+        assert_eq!(
+            root_reducer(&famine_state, &SimpleAction::Hunt.into()).food,
+            550
+        );
+        assert_eq!(
+            root_reducer(&abundant_state, &SimpleAction::Hunt.into()).food,
+            650
+        );
+    }
+
+    #[test]
+    fn test_bounty_from_seed() {
+        let bounties: Vec<Bounty> = (0..100).map(Bounty::from_seed).collect();
+
+        assert_eq!(
+            bounties,
+            (0..100).map(Bounty::from_seed).collect::<Vec<_>>()
+        );
+        assert!(bounties.contains(&Bounty::Famine));
+        assert!(bounties.contains(&Bounty::Normal));
+        assert!(bounties.contains(&Bounty::Abundance));
+    }
+
+    #[test]
+    fn test_bounty_doesnt_pick_first_leg() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        // play()'s first roll is the first leg's days, 3 to 6, so in any
+        // kind of year each should come up about a quarter of the time
+        for bounty in &[Bounty::Famine, Bounty::Normal, Bounty::Abundance] {
+            let seeds: Vec<u64> = (0..2000)
+                .filter(|&seed| Bounty::from_seed(seed) == *bounty)
+                .collect();
+            for days in 3..7 {
+                let legs = seeds
+                    .iter()
+                    .filter(|&&seed| StdRng::seed_from_u64(seed).gen_range(3, 7) == days)
+                    .count();
+                assert!(
+                    legs * 10 < seeds.len() * 4,
+                    "{:?} years start with {} days {} times in {}",
+                    bounty,
+                    days,
+                    legs,
+                    seeds.len()
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_options_from_args() {
+        let parse = |args: &[&str]| Options::from_args(args.iter().map(|arg| arg.to_string()));
+
+        assert_eq!(parse(&[]), Ok(Options::default()));
+        assert_eq!(
+            parse(&["--output", "json", "--skip-interludes"]),
+            Ok(Options {
+                show_interludes: false,
+                output: OutputMode::Json,
+                data_dir: None,
+                seed: None,
+            })
+        );
+        assert_eq!(parse(&["--seed", "1848"]).unwrap().seed, Some(1848));
+        assert!(parse(&["--seed", "-1"]).is_err());
+        assert!(parse(&["--seed"]).is_err());
+        assert_eq!(
+            parse(&["--data-dir", "/tmp/trail"]).unwrap().paths(),
+            Some(Paths::under("/tmp/trail"))
+        );
+        assert!(parse(&["--data-dir"]).is_err());
+        assert!(parse(&["--output"]).is_err());
+        assert!(parse(&["--output", "xml"]).is_err());
+        assert!(parse(&["--fast"]).is_err());
+    }
+
+    /// Options for a game without interludes, so only the turns are written
+    fn quiet(output: OutputMode) -> Options {
+        Options {
+            show_interludes: false,
+            output,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn test_play_until_oregon() {
+        let output = play_script("travel\n".repeat(100).as_bytes(), quiet(OutputMode::Json));
+        let turns: Vec<&str> = output.lines().collect();
+
+        // Every travel covers at least 30 miles, so 2000 miles takes 34 to 67
+        assert!(
+            turns.len() >= 35 && turns.len() <= 68,
+            "{} turns",
+            turns.len()
+        );
+        assert!(turns[0].ends_with(r#""choices":["travel","rest","hunt","options","upgrade"]}"#));
+        assert!(turns[1..turns.len() - 1]
+            .iter()
+            .all(|turn| turn.ends_with(r#""choices":["travel","rest","hunt","options"]}"#)));
+        assert!(turns[turns.len() - 1].contains("You made it to Oregon!"));
+        assert!(turns[turns.len() - 1].ends_with(r#""miles":0},"choices":[]}"#));
+    }
+
+    #[test]
+    fn test_play_is_reproducible_from_seed() {
+        let script = "travel\n".repeat(100);
+        let run = |seed| {
+            let options = Options {
+                seed: Some(seed),
+                ..quiet(OutputMode::Json)
+            };
+            play_script(script.as_bytes(), options)
+        };
+
+        assert_eq!(run(1848), run(1848));
+        assert_ne!(run(1848), run(1849));
+    }
+
+    #[test]
+    fn test_play_hunt_in_lean_and_good_years() {
+        let first_seed = |bounty| {
+            (0..)
+                .find(|&seed| Bounty::from_seed(seed) == bounty)
+                .unwrap()
+        };
+        let hunt = |bounty| {
+            let options = Options {
+                seed: Some(first_seed(bounty)),
+                ..quiet(OutputMode::Json)
+            };
+            play_script(b"hunt\n", options)
+        };
+
+        assert!(hunt(Bounty::Famine)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""food":550"#));
+        assert!(hunt(Bounty::Abundance)
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""food":650"#));
+    }
+
+    #[test]
+    fn test_play_upgrade_before_setting_out() {
+        let hunts = "hunt\n".repeat(12);
+        let upgraded = play_script(
+            format!("upgrade bed\n{}", hunts).as_bytes(),
+            quiet(OutputMode::Json),
+        );
+        let not_upgraded = play_script(hunts.as_bytes(), quiet(OutputMode::Json));
+
+        assert!(upgraded
+            .lines()
+            .nth(1)
+            .unwrap()
+            .contains(r#""larger_bed":true"#));
+        // Only changes are written, so the last food written is the most carried
+        assert!(upgraded.contains(r#""food":1500"#));
+        assert!(not_upgraded.contains(r#""food":1000"#));
+        assert!(!not_upgraded.contains(r#""food":1100"#));
+
+        let too_late = play_script(b"travel\nupgrade bed\n", quiet(OutputMode::Json));
+        let turns: Vec<&str> = too_late.lines().collect();
+        assert!(turns[2].contains("only be fitted out before setting out"));
+        assert!(!turns[2].contains("larger_bed"));
+        assert!(turns[2].ends_with(r#""choices":["travel","rest","hunt","options"]}"#));
+    }
+
+    #[test]
+    fn test_play_celebrates_on_time() {
+        // Steady travel reaches Independence Rock around the start of June
+        let output = play_script("travel\n".repeat(100).as_bytes(), quiet(OutputMode::Json));
+
+        assert_eq!(output.matches(r#""celebrated":true"#).count(), 1);
+    }
+
+    #[test]
+    fn test_play_toggles_interludes() {
+        let interludes = interludes::all();
+        let chimney_rock = &interludes
+            .iter()
+            .find(|i| i.id == "chimney-rock")
+            .unwrap()
+            .text;
+        let travels = "travel\n".repeat(100);
+
+        let turned_on = play_script(
+            format!("options interludes on\n{}", travels).as_bytes(),
+            quiet(OutputMode::Text),
+        );
+        assert!(turned_on.contains("Interludes are now on."));
+        assert!(turned_on.contains(chimney_rock));
+
+        let turned_off = play_script(
+            format!("options interludes off\n{}", travels).as_bytes(),
+            Options::default(),
+        );
+        assert!(turned_off.contains("Interludes are now off."));
+        assert!(!turned_off.contains(chimney_rock));
+    }
+
+    #[test]
+    fn test_play_until_input_ends() {
+        let output = play_script(b"rest 2\nrest 3\n", quiet(OutputMode::Json));
+        let turns: Vec<&str> = output.lines().collect();
+
+        assert_eq!(turns.len(), 3);
+        assert!(turns[2].contains(r#""date":"2020-03-06""#));
+        assert!(turns[2].ends_with(r#""choices":["travel","rest","hunt","options","upgrade"]}"#));
+    }
+
+    #[test]
+    fn test_play_past_bad_input() {
+        let output = play_script(b"\xff\xfe\nrest 2\n", quiet(OutputMode::Text));
+
+        assert!(output.contains("really weird"));
+        assert_eq!(output.matches("What is your action?").count(), 3);
+    }
+
+    #[test]
+    fn test_status() {
+        let default_state = StateBuilder::new().miles(1970).build();
         let status_mock_called = Cell::new(false);
 
         let status_mock = |state: State| -> State {
             status_mock_called.set(true);
-            return state;
+            state
         };
         assert_eq!(
             root_reducer(&default_state, &Action::Status(Box::new(status_mock))),
@@ -275,15 +909,9 @@ mod tests {
 
         let help_mock = |state: State| -> State {
             help_mock_called.set(true);
-            return state;
-        };
-        let default_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 1970,
-            food: 500,
-            health: 5,
-            hunt_days: 2,
+            state
         };
+        let default_state = StateBuilder::new().miles(1970).build();
         root_reducer(&default_state, &Action::Help(Box::new(help_mock)));
         assert!(help_mock_called.get());
     }
@@ -292,15 +920,9 @@ mod tests {
 
         let quit_mock = |state: State| -> State {
             quit_mock_called.set(true);
-            return state;
-        };
-        let default_state = State {
-            date: Utc.ymd(2020, 3, 1),
-            miles: 1970,
-            food: 500,
-            health: 5,
-            hunt_days: 2,
+            state
         };
+        let default_state = StateBuilder::new().miles(1970).build();
         root_reducer(&default_state, &Action::Quit(Box::new(quit_mock)));
         assert!(quit_mock_called.get());
     }
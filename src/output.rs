@@ -0,0 +1,170 @@
+use super::{save, State};
+use std::io::{self, Write};
+
+/// How play() writes each turn
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OutputMode {
+    /// Plain text for people
+    Text,
+    /// One JSON object per line for bots and test harnesses
+    Json,
+}
+
+/// Everything the player is shown between one command and the next
+pub struct Turn<'a> {
+    /// The State before the last command, or None on the first turn
+    pub before: Option<&'a State>,
+    pub after: &'a State,
+    pub messages: Vec<String>,
+    /// Commands the game will accept next; empty once it's over
+    pub choices: &'a [&'a str],
+}
+
+pub fn write_turn<W: Write>(output: &mut W, mode: OutputMode, turn: &Turn) -> io::Result<()> {
+    match mode {
+        OutputMode::Text => {
+            for message in &turn.messages {
+                writeln!(output, "{}", message)?;
+            }
+            Ok(())
+        }
+        OutputMode::Json => writeln!(output, "{}", to_json(turn)),
+    }
+}
+
+/// Render a turn as a single line of JSON
+///
+/// `state` only holds the fields that changed since `before`, or every field
+/// on the first turn.
+fn to_json(turn: &Turn) -> String {
+    let messages: Vec<String> = turn
+        .messages
+        .iter()
+        .map(|message| json_string(message.trim_end()))
+        .collect();
+    let choices: Vec<String> = turn.choices.iter().map(|c| json_string(c)).collect();
+    let fields = match turn.before {
+        Some(before) => state_fields(turn.after)
+            .into_iter()
+            .zip(state_fields(before))
+            .filter(|(after, before)| after != before)
+            .map(|(after, _)| after)
+            .collect(),
+        None => state_fields(turn.after),
+    };
+    let changes: Vec<String> = fields
+        .into_iter()
+        .map(|(name, value)| format!("{}:{}", json_string(name), value))
+        .collect();
+
+    format!(
+        "{{\"messages\":[{}],\"state\":{{{}}},\"choices\":[{}]}}",
+        messages.join(","),
+        changes.join(","),
+        choices.join(",")
+    )
+}
+
+/// Every field of a State as its name and JSON value
+fn state_fields(state: &State) -> Vec<(&'static str, String)> {
+    vec![
+        (
+            "date",
+            json_string(&state.date.format("%Y-%m-%d").to_string()),
+        ),
+        ("miles", state.miles.to_string()),
+        ("food", state.food.to_string()),
+        ("health", state.health.to_string()),
+        ("hunt_days", state.hunt_days.to_string()),
+        ("larger_bed", state.wagon.larger_bed.to_string()),
+        ("bounty", json_string(save::bounty_name(state.bounty))),
+        (
+            "departed",
+            json_string(&state.departed.format("%Y-%m-%d").to_string()),
+        ),
+        ("celebrated", state.celebrated.to_string()),
+    ]
+}
+
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{start_of_trail, StateBuilder};
+
+    #[test]
+    fn test_first_turn_has_whole_state() {
+        let state = start_of_trail();
+        let turn = Turn {
+            before: None,
+            after: &state,
+            messages: vec!["What is your action?".to_string()],
+            choices: &["travel"],
+        };
+
+        assert_eq!(
+            to_json(&turn),
+            concat!(
+                r#"{"messages":["What is your action?"],"#,
+                r#""state":{"date":"2020-03-01","miles":2000,"food":500,"health":5,"#,
+                r#""hunt_days":2,"larger_bed":false,"bounty":"normal","#,
+                r#""departed":"2020-03-01","celebrated":false},"#,
+                r#""choices":["travel"]}"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_later_turns_have_state_delta() {
+        let before = start_of_trail();
+        let after = StateBuilder::new().miles(1955).build();
+        let turn = Turn {
+            before: Some(&before),
+            after: &after,
+            messages: vec!["Say \"hi\"\n".to_string()],
+            choices: &[],
+        };
+
+        assert_eq!(
+            to_json(&turn),
+            r#"{"messages":["Say \"hi\""],"state":{"miles":1955},"choices":[]}"#
+        );
+    }
+
+    #[test]
+    fn test_json_string() {
+        assert_eq!(json_string("a\\b\tc\nd"), r#""a\\b\tc\nd""#);
+        assert_eq!(json_string("\u{1b}[0m"), r#""\u001b[0m""#);
+    }
+
+    #[test]
+    fn test_text_mode() {
+        let state = start_of_trail();
+        let turn = Turn {
+            before: None,
+            after: &state,
+            messages: vec!["One".to_string(), "Two".to_string()],
+            choices: &["travel"],
+        };
+        let mut output = Vec::new();
+        write_turn(&mut output, OutputMode::Text, &turn).unwrap();
+
+        assert_eq!(output, b"One\nTwo\n");
+    }
+}
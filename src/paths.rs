@@ -0,0 +1,165 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+const APP_NAME: &str = "oregon-trail";
+
+/// Where the game keeps its files on this machine
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Paths {
+    /// Holds the config file
+    pub config: PathBuf,
+    /// Holds saves, scores and mods, and is the root for FileSystemBackend
+    pub data: PathBuf,
+}
+
+impl Paths {
+    /// Keep everything under one directory, as --data-dir asks
+    pub fn under<P: Into<PathBuf>>(root: P) -> Paths {
+        let root = root.into();
+        Paths {
+            config: root.join("config"),
+            data: root,
+        }
+    }
+
+    /// The usual place for this platform, or None if the environment doesn't say
+    ///
+    /// - Linux and other Unixes: `$XDG_CONFIG_HOME` and `$XDG_DATA_HOME`,
+    ///   falling back to `~/.config` and `~/.local/share`
+    /// - Windows: `%APPDATA%`
+    /// - macOS: `~/Library/Application Support`
+    pub fn platform() -> Option<Paths> {
+        platform_paths(env::consts::OS, |name| env::var_os(name).map(PathBuf::from))
+    }
+
+    pub fn saves(&self) -> PathBuf {
+        self.data.join("saves")
+    }
+
+    pub fn scores(&self) -> PathBuf {
+        self.data.join("scores")
+    }
+
+    pub fn mods(&self) -> PathBuf {
+        self.data.join("mods")
+    }
+
+    /// Create every directory that doesn't exist yet
+    pub fn create_all(&self) -> io::Result<()> {
+        for directory in &[
+            self.config.clone(),
+            self.saves(),
+            self.scores(),
+            self.mods(),
+        ] {
+            fs::create_dir_all(directory)?;
+        }
+        Ok(())
+    }
+}
+
+fn platform_paths<F>(os: &str, var: F) -> Option<Paths>
+where
+    F: Fn(&str) -> Option<PathBuf>,
+{
+    // Relative values are ignored, as the XDG spec asks
+    let absolute = |name| var(name).filter(|path| path.is_absolute());
+    let home = absolute("HOME");
+
+    match os {
+        "windows" => absolute("APPDATA").map(|app_data| Paths::under(app_data.join(APP_NAME))),
+        "macos" => {
+            home.map(|home| Paths::under(home.join("Library/Application Support").join(APP_NAME)))
+        }
+        _ => {
+            let config =
+                absolute("XDG_CONFIG_HOME").or_else(|| home.clone().map(|h| h.join(".config")))?;
+            let data =
+                absolute("XDG_DATA_HOME").or_else(|| home.map(|h| h.join(".local/share")))?;
+            Some(Paths {
+                config: config.join(APP_NAME),
+                data: data.join(APP_NAME),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TempDir;
+
+    fn with_vars<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<PathBuf> + 'a {
+        move |name| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| PathBuf::from(value))
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_linux() {
+        assert_eq!(
+            platform_paths("linux", with_vars(&[("HOME", "/home/ezra")])),
+            Some(Paths {
+                config: PathBuf::from("/home/ezra/.config/oregon-trail"),
+                data: PathBuf::from("/home/ezra/.local/share/oregon-trail"),
+            })
+        );
+        assert_eq!(
+            platform_paths(
+                "linux",
+                with_vars(&[
+                    ("HOME", "/home/ezra"),
+                    ("XDG_CONFIG_HOME", "/etc/ezra"),
+                    ("XDG_DATA_HOME", "relative/data"),
+                ])
+            ),
+            Some(Paths {
+                config: PathBuf::from("/etc/ezra/oregon-trail"),
+                data: PathBuf::from("/home/ezra/.local/share/oregon-trail"),
+            })
+        );
+        assert_eq!(platform_paths("linux", with_vars(&[])), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_macos() {
+        assert_eq!(
+            platform_paths("macos", with_vars(&[("HOME", "/Users/ezra")])),
+            Some(Paths::under(
+                "/Users/ezra/Library/Application Support/oregon-trail"
+            ))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows() {
+        assert_eq!(
+            platform_paths(
+                "windows",
+                with_vars(&[("APPDATA", r"C:\Users\ezra\AppData\Roaming")])
+            ),
+            Some(Paths::under(r"C:\Users\ezra\AppData\Roaming\oregon-trail"))
+        );
+    }
+
+    #[test]
+    fn test_create_all() {
+        let root = TempDir::new("paths-test");
+        let paths = Paths::under(root.path());
+        paths.create_all().unwrap();
+        // Running again on later starts is fine
+        paths.create_all().unwrap();
+
+        assert!(paths.config.is_dir());
+        assert!(paths.saves().is_dir());
+        assert!(paths.scores().is_dir());
+        assert!(paths.mods().is_dir());
+    }
+}
@@ -0,0 +1,216 @@
+use super::{Bounty, State, Wagon};
+use chrono::prelude::*;
+use std::io::{self, BufRead, Write};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Write one State as a single line of a save file
+///
+/// States are written one per line, oldest first, so a whole history can be
+/// streamed to disk as it's played without building it up in memory first.
+pub fn write_state<W: Write>(writer: &mut W, state: &State) -> io::Result<()> {
+    writeln!(
+        writer,
+        "{} {} {} {} {} {} {} {} {}",
+        state.date.format(DATE_FORMAT),
+        state.miles,
+        state.food,
+        state.health,
+        state.hunt_days,
+        state.wagon.larger_bed,
+        bounty_name(state.bounty),
+        state.departed.format(DATE_FORMAT),
+        state.celebrated,
+    )
+}
+
+/// Write every State in a history, oldest first
+pub fn write_states<'a, W, I>(writer: &mut W, states: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a State>,
+{
+    for state in states {
+        write_state(writer, state)?;
+    }
+    writer.flush()
+}
+
+/// Parse a single line written by `write_state`
+pub fn read_state(line: &str) -> io::Result<State> {
+    let mut fields = line.split_whitespace();
+    let mut next = |name: &str| {
+        fields
+            .next()
+            .ok_or_else(|| invalid_data(format!("missing {}", name)))
+    };
+
+    let date = NaiveDate::parse_from_str(next("date")?, DATE_FORMAT)
+        .map_err(|error| invalid_data(format!("bad date: {}", error)))?;
+    let miles = parse_field(next("miles")?, "miles")?;
+    let food = parse_field(next("food")?, "food")?;
+    let health = parse_field(next("health")?, "health")?;
+    let hunt_days = parse_field(next("hunt_days")?, "hunt_days")?;
+    let larger_bed = parse_field(next("larger_bed")?, "larger_bed")?;
+    let bounty = parse_bounty(next("bounty")?)?;
+    let departed = NaiveDate::parse_from_str(next("departed")?, DATE_FORMAT)
+        .map_err(|error| invalid_data(format!("bad departed: {}", error)))?;
+    let celebrated = parse_field(next("celebrated")?, "celebrated")?;
+
+    Ok(State {
+        date: Date::from_utc(date, Utc),
+        departed: Date::from_utc(departed, Utc),
+        miles,
+        food,
+        health,
+        hunt_days,
+        wagon: Wagon { larger_bed },
+        bounty,
+        celebrated,
+    })
+}
+
+/// Lazily read a history back, one State per line
+///
+/// Blank lines are skipped so hand-edited saves still load.
+pub fn read_states<R: BufRead>(reader: R) -> impl Iterator<Item = io::Result<State>> {
+    reader
+        .lines()
+        .filter(|line| match line {
+            Ok(line) => !line.trim().is_empty(),
+            Err(_) => true,
+        })
+        .map(|line| line.and_then(|line| read_state(&line)))
+}
+
+/// Read a save and return only the most recent State
+pub fn load<R: BufRead>(reader: R) -> io::Result<State> {
+    let mut latest = None;
+    for state in read_states(reader) {
+        latest = Some(state?);
+    }
+    latest.ok_or_else(|| invalid_data("save file is empty".to_string()))
+}
+
+pub fn bounty_name(bounty: Bounty) -> &'static str {
+    match bounty {
+        Bounty::Famine => "famine",
+        Bounty::Normal => "normal",
+        Bounty::Abundance => "abundance",
+    }
+}
+
+fn parse_bounty(field: &str) -> io::Result<Bounty> {
+    match field {
+        "famine" => Ok(Bounty::Famine),
+        "normal" => Ok(Bounty::Normal),
+        "abundance" => Ok(Bounty::Abundance),
+        _ => Err(invalid_data(format!("bad bounty: {}", field))),
+    }
+}
+
+fn parse_field<T: std::str::FromStr>(field: &str, name: &str) -> io::Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    field
+        .parse()
+        .map_err(|error| invalid_data(format!("bad {}: {}", name, error)))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{travel_history, StateBuilder};
+    use std::time::{Duration, Instant};
+
+    // Comfortably more actions than a whole run takes
+    const FULL_RUN_LENGTH: usize = 500;
+
+    #[test]
+    fn test_round_trip() {
+        let states = travel_history(3);
+        let mut buffer = Vec::new();
+        write_states(&mut buffer, &states).unwrap();
+
+        let loaded: Vec<State> = read_states(&buffer[..]).collect::<io::Result<_>>().unwrap();
+        assert_eq!(loaded, states);
+        assert_eq!(load(&buffer[..]).unwrap(), states[2]);
+    }
+
+    #[test]
+    fn test_round_trip_everything() {
+        let state = StateBuilder::new()
+            .wagon(Wagon { larger_bed: true })
+            .bounty(Bounty::Famine)
+            .departed(Utc.ymd(2021, 4, 15))
+            .celebrated(true)
+            .build();
+        let mut buffer = Vec::new();
+        write_state(&mut buffer, &state).unwrap();
+
+        assert_eq!(load(&buffer[..]).unwrap(), state);
+    }
+
+    #[test]
+    fn test_large_history() {
+        let states = travel_history(10_000);
+        let mut buffer = Vec::new();
+        write_states(&mut buffer, &states).unwrap();
+
+        assert_eq!(read_states(&buffer[..]).count(), states.len());
+        assert_eq!(load(&buffer[..]).unwrap(), states[states.len() - 1]);
+    }
+
+    #[test]
+    fn test_bad_save() {
+        assert!(load(&b""[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500\n"[..]).is_err());
+        assert!(load(&b"2020-13-01 2000 500 5 2 false normal 2020-03-01 false\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 lots 5 2 false normal 2020-03-01 false\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 maybe normal 2020-03-01 false\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 false plenty 2020-03-01 false\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 false normal\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 false normal 2020-03-01\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 false normal 2020-03-01 yes\n"[..]).is_err());
+        assert!(load(&b"2020-03-01 2000 500 5 2 false normal 2020-02-30 false\n"[..]).is_err());
+    }
+
+    // Run before a release with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn test_full_run_save_and_load_speed() {
+        let states = travel_history(FULL_RUN_LENGTH);
+
+        // Best of several rounds, so a busy machine doesn't fail the test
+        let fastest = (0..10)
+            .map(|_| {
+                let started = Instant::now();
+                let mut buffer = Vec::new();
+                write_states(&mut buffer, &states).unwrap();
+                let loaded = load(&buffer[..]).unwrap();
+                let elapsed = started.elapsed();
+
+                assert_eq!(loaded, states[FULL_RUN_LENGTH - 1]);
+                elapsed
+            })
+            .min()
+            .unwrap();
+
+        // Unoptimized builds get more room, but still catch anything quadratic
+        let limit = if cfg!(debug_assertions) {
+            Duration::from_millis(25)
+        } else {
+            Duration::from_millis(5)
+        };
+        assert!(
+            fastest < limit,
+            "saving and loading a full run took {:?}",
+            fastest
+        );
+    }
+}
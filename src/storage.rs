@@ -0,0 +1,195 @@
+use super::{save, State};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Somewhere saves can be kept, addressed by slash-separated keys
+///
+/// Game code only talks to this trait, so saves can go to disk, memory, or
+/// anywhere else without the game knowing.
+pub trait StorageBackend {
+    /// Replace whatever is stored under `key` with what `contents` writes
+    ///
+    /// The old value is only replaced once `contents` has succeeded, so a
+    /// failed write never loses what was there before.
+    fn write(&mut self, key: &str, contents: Contents) -> io::Result<()>;
+
+    /// Read back what's stored under `key`, failing with NotFound if nothing is
+    fn reader(&self, key: &str) -> io::Result<Box<dyn BufRead + '_>>;
+}
+
+/// Streams a new value into the writer a StorageBackend hands it
+pub type Contents<'a> = Box<dyn FnOnce(&mut dyn Write) -> io::Result<()> + 'a>;
+
+/// Stores each key as a file under a root directory
+pub struct FileSystemBackend {
+    root: PathBuf,
+}
+
+impl FileSystemBackend {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        FileSystemBackend { root: root.into() }
+    }
+}
+
+impl StorageBackend for FileSystemBackend {
+    fn write(&mut self, key: &str, contents: Contents) -> io::Result<()> {
+        let path = self.root.join(key);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Write beside the real file, then swap it in. The leading dot keeps
+        // temp files out of the names save_key allows.
+        let mut temp_name = std::ffi::OsString::from(".");
+        temp_name.push(path.file_name().unwrap_or_default());
+        temp_name.push(".tmp");
+        let temp_path = path.with_file_name(temp_name);
+
+        let result = write_file(&temp_path, contents).and_then(|_| fs::rename(&temp_path, &path));
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    fn reader(&self, key: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        Ok(Box::new(BufReader::new(File::open(self.root.join(key))?)))
+    }
+}
+
+fn write_file(path: &Path, contents: Contents) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    contents(&mut writer)?;
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error())?
+        .sync_all()
+}
+
+/// Keeps everything in memory, for tests and anywhere without a disk
+#[derive(Debug, Default)]
+pub struct MemoryBackend {
+    entries: HashMap<String, Vec<u8>>,
+}
+
+impl StorageBackend for MemoryBackend {
+    fn write(&mut self, key: &str, contents: Contents) -> io::Result<()> {
+        let mut entry = Vec::new();
+        contents(&mut entry)?;
+        self.entries.insert(key.to_string(), entry);
+        Ok(())
+    }
+
+    fn reader(&self, key: &str) -> io::Result<Box<dyn BufRead + '_>> {
+        match self.entries.get(key) {
+            Some(entry) => Ok(Box::new(&entry[..])),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("nothing stored under {}", key),
+            )),
+        }
+    }
+}
+
+/// The key for a save slot, refusing names that could reach outside saves/
+///
+/// Names starting with a dot are refused too: "." is saves/ itself, and
+/// FileSystemBackend keeps its temp files under dotted names.
+fn save_key(slot: &str) -> io::Result<String> {
+    if slot.is_empty()
+        || slot.starts_with('.')
+        || slot.contains('/')
+        || slot.contains('\\')
+        || slot.contains("..")
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{:?} can't be used as a save slot", slot),
+        ));
+    }
+    Ok(format!("saves/{}", slot))
+}
+
+/// Save a game's history to a save slot
+pub fn save_game<'a, B, I>(backend: &mut B, slot: &str, states: I) -> io::Result<()>
+where
+    B: StorageBackend + ?Sized,
+    I: IntoIterator<Item = &'a State> + 'a,
+{
+    let key = save_key(slot)?;
+    backend.write(
+        &key,
+        Box::new(move |mut writer| save::write_states(&mut writer, states)),
+    )
+}
+
+/// Load the latest State from a save slot
+pub fn load_game<B: StorageBackend + ?Sized>(backend: &B, slot: &str) -> io::Result<State> {
+    save::load(backend.reader(&save_key(slot)?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{travel_history, TempDir};
+
+    fn check_backend<B: StorageBackend>(backend: &mut B) {
+        let states = travel_history(3);
+        let shorter_states = travel_history(2);
+
+        assert_eq!(
+            load_game(backend, "one").unwrap_err().kind(),
+            io::ErrorKind::NotFound
+        );
+
+        save_game(backend, "one", &states).unwrap();
+        save_game(backend, "two", &shorter_states).unwrap();
+        assert_eq!(load_game(backend, "one").unwrap(), states[2]);
+        assert_eq!(load_game(backend, "two").unwrap(), shorter_states[1]);
+
+        // Saving again replaces the old save rather than adding to it
+        save_game(backend, "one", &shorter_states).unwrap();
+        assert_eq!(load_game(backend, "one").unwrap(), shorter_states[1]);
+
+        // A write that fails partway leaves the old save alone
+        let failed = backend.write(
+            "saves/one",
+            Box::new(|writer| {
+                writer.write_all(b"2020-03-")?;
+                Err(io::Error::other("disk full"))
+            }),
+        );
+        assert!(failed.is_err());
+        assert_eq!(load_game(backend, "one").unwrap(), shorter_states[1]);
+
+        for slot in &["", "../../x", "saves/one", "a\\b", "..", ".", ".one.tmp"] {
+            assert_eq!(
+                save_game(backend, slot, &states).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+            assert_eq!(
+                load_game(backend, slot).unwrap_err().kind(),
+                io::ErrorKind::InvalidInput
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_backend() {
+        check_backend(&mut MemoryBackend::default());
+    }
+
+    #[test]
+    fn test_file_system_backend() {
+        let root = TempDir::new("storage-test");
+        check_backend(&mut FileSystemBackend::new(root.path()));
+
+        let files: Vec<_> = fs::read_dir(root.path().join("saves"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files.len(), 2, "temp files left behind: {:?}", files);
+    }
+}
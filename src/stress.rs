@@ -0,0 +1,208 @@
+//! Drives long runs of random actions through the reducer across many seeds,
+//! checking invariants after every action, and random command lines through
+//! play() to check whatever the player types is handled.
+
+use super::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::panic;
+
+const QUICK_SEEDS: u64 = 500;
+const RELEASE_SEEDS: u64 = 10_000;
+// One action in seven is travel, averaging 44.5 miles, so a run is about
+// 45 travels or 315 actions; anything near this is stuck
+const MAX_ACTIONS_PER_RUN: u64 = 10_000;
+// Games through play() print every turn, so fewer of them
+const QUICK_GAMES: u64 = 50;
+const RELEASE_GAMES: u64 = 1_000;
+// Two lines in nine are travel, so a game needs about 225
+const LINES_PER_GAME: usize = 2_000;
+
+fn random_action<'a>(rng: &mut StdRng) -> Action<'a> {
+    match rng.gen_range(0, 7) {
+        0 => Action::Travel(Duration::days(rng.gen_range(3, 7)), rng.gen_range(30, 60)),
+        1 => Action::Rest(Duration::days(rng.gen_range(1, 5))),
+        2 => Action::Hunt,
+        3 => Action::Status(Box::new(|state| state)),
+        4 => Action::Help(Box::new(|state| state)),
+        5 => Action::Upgrade(Upgrade::LargerBed),
+        _ => Action::Quit(Box::new(|state| state)),
+    }
+}
+
+/// A line the player might type, sane or not, without its newline
+fn random_line(rng: &mut StdRng) -> Vec<u8> {
+    match rng.gen_range(0, 9) {
+        0 | 1 => b"travel".to_vec(),
+        2 => b"hunt".to_vec(),
+        3 => format!("rest {}", rng.gen_range(-50, 50)).into_bytes(),
+        4 => format!("rest {}{}", rng.gen::<u64>(), rng.gen::<u64>()).into_bytes(),
+        5 => {
+            let junk = [
+                "",
+                "   ",
+                "hunt deer",
+                "dance",
+                "rest",
+                "rest -",
+                "rest 2.5",
+                "rest lots",
+                "rest 3 days",
+                "travel far",
+                "TRAVEL",
+                "upgrade",
+                "upgrade bed",
+                "upgrade wings",
+                "options",
+                "options interludes on",
+                "options interludes off",
+                "options color on",
+            ];
+            junk[rng.gen_range(0, junk.len())].as_bytes().to_vec()
+        }
+        6 => format!(
+            "{} \u{1b}[2J\u{7}",
+            ["travel", "rest", "hunt"][rng.gen_range(0, 3)]
+        )
+        .into_bytes(),
+        7 => format!("rest {}", "9".repeat(rng.gen_range(1, 200))).into_bytes(),
+        // Any bytes at all, including ones that aren't UTF-8
+        _ => (0..rng.gen_range(0, 20))
+            .map(|_| rng.gen::<u8>())
+            .filter(|&byte| byte != b'\n')
+            .collect(),
+    }
+}
+
+/// Check what must hold between any State and the one after it
+fn check_invariants(before: &State, after: &State) -> Result<(), String> {
+    if after.health > 5 {
+        return Err(format!("health went above 5 to {}", after.health));
+    }
+    if after.miles > before.miles {
+        return Err(format!(
+            "miles went back up from {} to {}",
+            before.miles, after.miles
+        ));
+    }
+    if after.date < before.date {
+        return Err(format!(
+            "date went backwards from {} to {}",
+            before.date, after.date
+        ));
+    }
+    if after.food < before.food {
+        return Err(format!(
+            "food went down from {} to {}",
+            before.food, after.food
+        ));
+    }
+    if before.celebrated && !after.celebrated {
+        return Err("the Independence Day bonus was taken away".to_string());
+    }
+    if after.food > after.wagon.capacity().max(before.food) {
+        return Err(format!(
+            "food went over the wagon's capacity to {}",
+            after.food
+        ));
+    }
+    Ok(())
+}
+
+/// Play one game to the end, returning how many actions it took
+fn run(seed: u64) -> Result<u64, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut state = crate::testing::StateBuilder::new()
+        .bounty(Bounty::from_seed(seed))
+        .build();
+
+    for actions in 1..=MAX_ACTIONS_PER_RUN {
+        let action = random_action(&mut rng);
+        let next = root_reducer(&state, &action);
+        check_invariants(&state, &next)?;
+        state = next;
+
+        if state.miles == 0 {
+            return Ok(actions);
+        }
+    }
+    Err(format!(
+        "still {} miles out after {} actions",
+        state.miles, MAX_ACTIONS_PER_RUN
+    ))
+}
+
+/// Play a game for every seed, panicking with the seed that broke
+fn run_seeds(seeds: u64) -> u64 {
+    let mut total_actions = 0;
+
+    for seed in 0..seeds {
+        match panic::catch_unwind(|| run(seed)) {
+            Ok(Ok(actions)) => total_actions += actions,
+            Ok(Err(message)) => panic!("seed {}: {}", seed, message),
+            Err(_) => panic!("seed {} panicked", seed),
+        }
+    }
+    total_actions
+}
+
+/// Play one game through play() on random command lines
+fn play_commands(seed: u64) -> Result<(), String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut script = Vec::new();
+    for _ in 0..LINES_PER_GAME {
+        script.extend(random_line(&mut rng));
+        script.push(b'\n');
+    }
+
+    let options = Options {
+        show_interludes: rng.gen(),
+        seed: Some(seed),
+        ..Options::default()
+    };
+    let output = crate::testing::play_script(&script, options);
+
+    let violations = crate::audit::audit(&output);
+    if !violations.is_empty() {
+        return Err(violations.join("\n"));
+    }
+    if !output.ends_with("You made it to Oregon!\n") {
+        return Err(format!("didn't reach Oregon in {} lines", LINES_PER_GAME));
+    }
+    Ok(())
+}
+
+/// Play a game through play() for every seed, panicking with the seed that broke
+fn play_seeds(seeds: u64) {
+    for seed in 0..seeds {
+        match panic::catch_unwind(|| play_commands(seed)) {
+            Ok(Ok(())) => {}
+            Ok(Err(message)) => panic!("seed {}: {}", seed, message),
+            Err(_) => panic!("seed {} panicked", seed),
+        }
+    }
+}
+
+#[test]
+fn test_random_runs() {
+    run_seeds(QUICK_SEEDS);
+}
+
+// Millions of actions; run before a release with `cargo test --release -- --ignored`
+#[test]
+#[ignore]
+fn test_random_runs_release() {
+    assert!(run_seeds(RELEASE_SEEDS) >= 1_000_000);
+}
+
+#[test]
+fn test_random_commands() {
+    play_seeds(QUICK_GAMES);
+}
+
+// A thousand whole games; run along with the one above
+#[test]
+#[ignore]
+fn test_random_commands_release() {
+    play_seeds(RELEASE_GAMES);
+}
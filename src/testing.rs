@@ -0,0 +1,147 @@
+use super::{new_game, play, Bounty, Options, State, Wagon};
+use chrono::prelude::*;
+use chrono::Duration;
+use std::path::{Path, PathBuf};
+use std::{env, fs, process};
+
+/// Build a State for tests, starting from the beginning of the trail
+///
+/// Only the fields a test cares about need to be set, so adding a field to
+/// State only means giving it a default here.
+///
+/// # Examples
+/// ```
+/// let state = StateBuilder::new().miles(1970).health(4).build();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct StateBuilder {
+    state: State,
+}
+
+impl StateBuilder {
+    pub fn new() -> Self {
+        StateBuilder {
+            state: State {
+                bounty: Bounty::Normal,
+                ..new_game(0)
+            },
+        }
+    }
+
+    pub fn date(mut self, date: Date<Utc>) -> Self {
+        self.state.date = date;
+        self
+    }
+
+    pub fn departed(mut self, departed: Date<Utc>) -> Self {
+        self.state.departed = departed;
+        self
+    }
+
+    pub fn miles(mut self, miles: u64) -> Self {
+        self.state.miles = miles;
+        self
+    }
+
+    pub fn food(mut self, food: u64) -> Self {
+        self.state.food = food;
+        self
+    }
+
+    pub fn health(mut self, health: u64) -> Self {
+        self.state.health = health;
+        self
+    }
+
+    pub fn hunt_days(mut self, hunt_days: i64) -> Self {
+        self.state.hunt_days = hunt_days;
+        self
+    }
+
+    pub fn wagon(mut self, wagon: Wagon) -> Self {
+        self.state.wagon = wagon;
+        self
+    }
+
+    pub fn bounty(mut self, bounty: Bounty) -> Self {
+        self.state.bounty = bounty;
+        self
+    }
+
+    pub fn celebrated(mut self, celebrated: bool) -> Self {
+        self.state.celebrated = celebrated;
+        self
+    }
+
+    pub fn build(self) -> State {
+        self.state
+    }
+}
+
+impl Default for StateBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The State a new game starts with
+pub fn start_of_trail() -> State {
+    StateBuilder::new().build()
+}
+
+/// `length` States of steady travel, one day and ten miles apart
+pub fn travel_history(length: usize) -> Vec<State> {
+    let mut state = start_of_trail();
+    let mut states = vec![state];
+    for _ in 1..length {
+        state = State {
+            date: state.date + Duration::days(1),
+            miles: state.miles.saturating_sub(10),
+            ..state
+        };
+        states.push(state);
+    }
+    states
+}
+
+/// Play a game with `script` as the player's input and return its output
+///
+/// With a seed the game starts in that seed's year, as main() would start it;
+/// without one it starts from `start_of_trail()`.
+pub fn play_script(script: &[u8], options: Options) -> String {
+    let state = match options.seed {
+        Some(seed) => new_game(seed),
+        None => start_of_trail(),
+    };
+    let mut output = Vec::new();
+    play(&mut &script[..], &mut output, state, options).unwrap();
+    String::from_utf8(output).expect("output should be UTF-8")
+}
+
+/// A fresh directory under the system temp dir, removed again when dropped
+///
+/// Drop also runs when a test panics, so a failed test doesn't leave the
+/// directory behind.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// `name` keeps tests running at the same time out of each other's way
+    pub fn new(name: &str) -> TempDir {
+        let path = env::temp_dir().join(format!("oregon-trail-{}-{}", name, process::id()));
+        // Left over from an earlier run that was killed before Drop
+        let _ = fs::remove_dir_all(&path);
+        TempDir { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}